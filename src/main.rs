@@ -2,25 +2,162 @@ use borsh::de::BorshDeserialize;
 use gumdrop::Options;
 use metaplex_token_metadata::{
     instruction::update_metadata_accounts,
-    state::{Metadata, Data, Creator},
+    state::{Creator, Data, Metadata, MAX_CREATOR_LIMIT},
 };
-use rusqlite::{params, Connection};
+use rand::Rng;
+use rusqlite::{params, Connection, OptionalExtension};
 use serde::Deserialize;
 use serde_json::json;
-use solana_account_decoder::UiAccountEncoding;
+use solana_account_decoder::{UiAccountEncoding, UiDataSliceConfig};
 use solana_client::{
+    client_error::{ClientError, ClientErrorKind},
     rpc_client::RpcClient,
-    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+    rpc_config::{
+        GetConfirmedSignaturesForAddress2Config, RpcAccountInfoConfig, RpcProgramAccountsConfig,
+    },
     rpc_filter::{Memcmp, MemcmpEncodedBytes, RpcFilterType},
     rpc_request::RpcRequest,
     rpc_response::Response,
 };
 use solana_sdk::{
     account::ReadableAccount, program_pack::Pack, pubkey::Pubkey, signature::read_keypair_file,
-    signer::Signer, transaction::Transaction,
+    signature::Signature, signer::Signer, transaction::Transaction,
 };
+use solana_transaction_status::{EncodedConfirmedTransactionWithStatusMeta, UiTransactionEncoding};
 use spl_token::state::Account;
-use std::{error::Error, time::Duration};
+use std::{error::Error, thread, time::Duration};
+
+/// Which SPL token program a mint or token account belongs to.
+///
+/// Token-2022 accounts prepend the same base `Account` fields used by the
+/// legacy program before their TLV extension data, so the first
+/// [`Account::LEN`] bytes unpack identically regardless of which variant
+/// owns the account.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum TokenProgram {
+    Legacy,
+    Token2022,
+}
+
+impl TokenProgram {
+    const ALL: [TokenProgram; 2] = [TokenProgram::Legacy, TokenProgram::Token2022];
+
+    fn id(&self) -> Pubkey {
+        match self {
+            TokenProgram::Legacy => spl_token::id(),
+            TokenProgram::Token2022 => spl_token_2022::id(),
+        }
+    }
+
+    fn from_program_id(program_id: &Pubkey) -> Option<TokenProgram> {
+        if *program_id == spl_token::id() {
+            Some(TokenProgram::Legacy)
+        } else if *program_id == spl_token_2022::id() {
+            Some(TokenProgram::Token2022)
+        } else {
+            None
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            TokenProgram::Legacy => "spl-token",
+            TokenProgram::Token2022 => "spl-token-2022",
+        }
+    }
+}
+
+/// Unpacks the base token-account layout shared by both token programs,
+/// ignoring any Token-2022 TLV extension bytes that follow it.
+fn unpack_token_account(program: TokenProgram, data: &[u8]) -> Result<Account, Box<dyn Error>> {
+    if data.len() < Account::LEN {
+        return Err(format!(
+            "{} token account data too short ({} bytes)",
+            program.as_str(),
+            data.len()
+        )
+        .into());
+    }
+    Ok(Account::unpack(&data[..Account::LEN])?)
+}
+
+/// Byte length of a metadata account's `key`, `update_authority`, `mint`,
+/// `name`, `symbol` and `uri` fields in the worst case (every string at its
+/// Metaplex max length), used to `dataSlice` just that header off the
+/// account instead of fetching the whole body.
+const METADATA_HEADER_LEN: u64 = 1 // key
+    + 32 // update_authority
+    + 32 // mint
+    + 4 + 32 // name (length prefix + MAX_NAME_LENGTH)
+    + 4 + 10 // symbol (length prefix + MAX_SYMBOL_LENGTH)
+    + 4 + 200; // uri (length prefix + MAX_URI_LENGTH)
+
+/// Reads the `mint` and `uri` fields out of a metadata account's leading
+/// bytes, without deserializing the rest of the account (creators, edition
+/// info, etc.) that `Metadata::deserialize` would otherwise require.
+fn parse_mint_and_uri(mut data: &[u8]) -> Result<(Pubkey, String), Box<dyn Error>> {
+    u8::deserialize(&mut data)?; // key
+    Pubkey::deserialize(&mut data)?; // update_authority
+    let mint = Pubkey::deserialize(&mut data)?;
+    String::deserialize(&mut data)?; // name
+    String::deserialize(&mut data)?; // symbol
+    let uri = String::deserialize(&mut data)?;
+    Ok((mint, uri.trim_matches(char::from(0)).to_owned()))
+}
+
+/// Cap on the randomized backoff delay, regardless of attempt count.
+const MAX_BACKOFF_MS: u64 = 30_000;
+
+/// Runs `op`, retrying on transient RPC errors with full-jitter exponential
+/// backoff (delay doubles per attempt, capped, then randomized down to
+/// spread out retries) up to `max_retries` times. Permanent errors (bad
+/// params, missing accounts, serialization failures) are returned
+/// immediately without retrying.
+///
+/// Does not honor a server-provided `Retry-After` cool-off: `ClientError`
+/// (solana_client) does not expose the response headers a 429 would carry
+/// it in, only the status-line/body text, so there is nothing to read
+/// here. Every wait is the computed jittered backoff below.
+fn retry<T>(
+    max_retries: usize,
+    base_delay_ms: u64,
+    mut op: impl FnMut() -> Result<T, ClientError>,
+) -> Result<T, Box<dyn Error>> {
+    let mut attempt = 0;
+    loop {
+        match op() {
+            Ok(value) => return Ok(value),
+            Err(err) if attempt < max_retries && is_retryable(&err) => {
+                attempt += 1;
+                eprint!("!");
+                thread::sleep(backoff_delay(base_delay_ms, attempt));
+            }
+            Err(err) => return Err(err.into()),
+        }
+    }
+}
+
+fn is_rate_limited(err: &ClientError) -> bool {
+    let message = err.to_string().to_lowercase();
+    message.contains("429")
+        || message.contains("rate limit")
+        || message.contains("too many requests")
+}
+
+fn is_retryable(err: &ClientError) -> bool {
+    match err.kind() {
+        ClientErrorKind::Io(_) | ClientErrorKind::Reqwest(_) => true,
+        ClientErrorKind::RpcError(_) => is_rate_limited(err),
+        _ => false,
+    }
+}
+
+fn backoff_delay(base_delay_ms: u64, attempt: usize) -> Duration {
+    let cap_ms = base_delay_ms
+        .saturating_mul(1u64 << attempt.min(10))
+        .min(MAX_BACKOFF_MS);
+    Duration::from_millis(rand::thread_rng().gen_range(0..=cap_ms))
+}
 
 #[derive(Clone, Debug, Options)]
 struct Args {
@@ -28,6 +165,13 @@ struct Args {
     db: String,
     #[options(help = "rpc server", default_expr = "default_rpc_url()", meta = "r")]
     rpc: String,
+    #[options(help = "max rpc retries", default_expr = "default_max_retries()")]
+    max_retries: usize,
+    #[options(
+        help = "base retry delay in ms (jittered backoff only; a 429's Retry-After header, if any, is not honored)",
+        default_expr = "default_base_delay_ms()"
+    )]
+    base_delay_ms: u64,
     #[options(command)]
     command: Option<Command>,
 }
@@ -40,12 +184,21 @@ fn default_rpc_url() -> String {
     "https://api.mainnet-beta.solana.com".to_owned()
 }
 
+fn default_max_retries() -> usize {
+    5
+}
+
+fn default_base_delay_ms() -> u64 {
+    250
+}
+
 #[derive(Clone, Debug, Options)]
 enum Command {
     MineHolders(MineHolders),
     MineMetadata(MineMetadata),
+    MineCompressed(MineCompressed),
     ListMetadataUris(ListMetadataUris),
-    // MineTransactions(MineTransactions),
+    MineTransactions(MineTransactions),
     RescueSlatts(RescueSlatts),
 }
 
@@ -67,17 +220,147 @@ struct MineHolders {
     creator_address: String,
 }
 
+#[derive(Clone, Debug, Options)]
+struct MineCompressed {
+    #[options(help = "creator address")]
+    creator_address: String,
+}
+
 #[derive(Clone, Debug, Options)]
 struct RescueSlatts {
     #[options(help = "update authority keypair")]
     update_authority: String,
+    #[options(help = "sign and submit the rewrite instead of only simulating it")]
+    commit: bool,
+    #[options(help = "path to a JSON file of creator-rewrite rules, see RewriteRule")]
+    rules: Option<String>,
+    #[options(help = "verified creator address for a catch-all rule, used only without --rules")]
+    new_creator: Option<String>,
+    #[options(
+        help = "fallback share (0-100) for --new-creator",
+        default_expr = "default_new_creator_share()"
+    )]
+    new_creator_share: u8,
+}
+
+fn default_new_creator_share() -> u8 {
+    100
+}
+
+/// A creator-rewrite rule: metadata whose current creator set matches
+/// `match_creators` (in any order) is rewritten to `creators`. An empty
+/// `match_creators` matches any creator set, for a catch-all fallback rule.
+#[derive(Clone, Debug, Deserialize)]
+struct RewriteRule {
+    #[serde(default)]
+    match_creators: Vec<String>,
+    creators: Vec<RuleCreator>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct RuleCreator {
+    address: String,
+    share: u8,
+    #[serde(default)]
+    verified: bool,
+}
+
+/// Loads rewrite rules from `--rules`, falling back to a single catch-all
+/// rule built from `--new-creator`/`--new-creator-share` (mirroring the
+/// crate's old hardcoded collapse-to-one-creator behavior) when no rules
+/// file is given.
+fn load_rewrite_rules(opts: &RescueSlatts) -> Result<Vec<RewriteRule>, Box<dyn Error>> {
+    if let Some(path) = &opts.rules {
+        let contents = std::fs::read_to_string(path)?;
+        return Ok(serde_json::from_str(&contents)?);
+    }
+
+    match &opts.new_creator {
+        Some(new_creator) => Ok(vec![RewriteRule {
+            match_creators: Vec::new(),
+            creators: vec![RuleCreator {
+                address: new_creator.clone(),
+                share: opts.new_creator_share,
+                verified: true,
+            }],
+        }]),
+        None => Ok(Vec::new()),
+    }
+}
+
+fn find_rewrite_rule<'a>(
+    rules: &'a [RewriteRule],
+    creators: &[Creator],
+) -> Option<&'a RewriteRule> {
+    let mut current: Vec<String> = creators.iter().map(|c| c.address.to_string()).collect();
+    current.sort();
+    rules.iter().find(|rule| {
+        if rule.match_creators.is_empty() {
+            return true;
+        }
+        let mut expected = rule.match_creators.clone();
+        expected.sort();
+        expected == current
+    })
+}
+
+/// Validates a rule's creator set against the Metaplex constraints the
+/// on-chain program enforces, so a malformed `--rules` file fails at load
+/// rather than mid-run on whichever metadata happens to match it first.
+///
+/// A rule can only mark a creator `verified: true` for `update_authority`
+/// itself: the on-chain program signs off a creator's `verified` flag by
+/// checking that creator against the transaction's signers, and
+/// `update_metadata_accounts` only ever signs with `update_authority`, so
+/// marking any other address verified would just fail on submission.
+fn validate_rule(
+    creators: &[RuleCreator],
+    update_authority: &Pubkey,
+) -> Result<(), Box<dyn Error>> {
+    if creators.is_empty() || creators.len() > MAX_CREATOR_LIMIT {
+        return Err(format!(
+            "rewrite must have between 1 and {} creators, got {}",
+            MAX_CREATOR_LIMIT,
+            creators.len()
+        )
+        .into());
+    }
+    let total_share: u32 = creators.iter().map(|c| c.share as u32).sum();
+    if total_share != 100 {
+        return Err(format!("creator shares must sum to 100, got {}", total_share).into());
+    }
+    for creator in creators {
+        if !creator.verified {
+            continue;
+        }
+        let address: Pubkey = creator.address.parse()?;
+        if address != *update_authority {
+            return Err(format!(
+                "creator {} is marked verified but is not the signing update_authority {}",
+                creator.address, update_authority
+            )
+            .into());
+        }
+    }
+    Ok(())
 }
 
-// #[derive(Clone, Debug, Options)]
-// struct MineTransactions {
-//     #[options(help = "account id")]
-//     account_id: String,
-// }
+/// Validates the one constraint that depends on the on-chain metadata
+/// itself rather than the rule, so it can only be checked per-row once the
+/// account has been fetched.
+fn validate_seller_fee_basis_points(seller_fee_basis_points: u16) -> Result<(), Box<dyn Error>> {
+    if seller_fee_basis_points > 10000 {
+        return Err(format!(
+            "seller_fee_basis_points {} exceeds the 10000 max",
+            seller_fee_basis_points
+        )
+        .into());
+    }
+    Ok(())
+}
+
+#[derive(Clone, Debug, Options)]
+struct MineTransactions {}
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
@@ -88,41 +371,55 @@ async fn main() -> Result<(), Box<dyn Error>> {
             Command::ListMetadataUris(opts) => list_metadata_uris(args, opts).await,
             Command::MineHolders(opts) => mine_holders(args, opts).await,
             Command::MineMetadata(opts) => mine_metadata(args, opts).await,
+            Command::MineCompressed(opts) => mine_compressed(args, opts).await,
+            Command::MineTransactions(opts) => mine_transactions(args, opts).await,
             Command::RescueSlatts(opts) => rescue_slatts(args, opts).await,
         },
     }
 }
 
+/// Max number of pubkeys the `getMultipleAccounts` RPC accepts in one call.
+const MAX_MULTIPLE_ACCOUNTS: usize = 100;
+
 async fn list_metadata_uris(args: Args, _opts: ListMetadataUris) -> Result<(), Box<dyn Error>> {
+    let max_retries = args.max_retries;
+    let base_delay_ms = args.base_delay_ms;
+
     let db = Connection::open(args.db)?;
 
     let timeout = Duration::from_secs(500); // TODO read from Args?
     let rpc = RpcClient::new_with_timeout(args.rpc, timeout);
 
     let mut stmt = db.prepare("SELECT metadata_address FROM metadata")?;
-    let mut rows = stmt.query([])?;
-    while let Some(row) = rows.next()? {
-        let metadata_address: String = row.get(0)?;
+    let metadata_addresses = stmt
+        .query_map([], |row| row.get::<_, String>(0))?
+        .collect::<Result<Vec<String>, _>>()?;
 
-        let mut tries = 0;
-        let account = loop {
-            tries += 1;
-            match rpc.get_account(&metadata_address.parse()?) {
-                Ok(account) => break Some(account),
-                Err(err) => {
-                    eprint!("!");
-                    if tries > 5 {
-                        eprintln!("{} {}", metadata_address, err);
-                        break None;
-                    }
-                }
-            }
-        };
+    let config = RpcAccountInfoConfig {
+        encoding: Some(UiAccountEncoding::Base64),
+        data_slice: Some(UiDataSliceConfig {
+            offset: 0,
+            length: METADATA_HEADER_LEN as usize,
+        }),
+        ..RpcAccountInfoConfig::default()
+    };
 
-        if let Some(account) = account {
-            let metadata = Metadata::deserialize(&mut account.data())?;
-            let uri = metadata.data.uri.trim_matches(char::from(0));
-            println!("{},{}", metadata_address, uri);
+    for chunk in metadata_addresses.chunks(MAX_MULTIPLE_ACCOUNTS) {
+        let pubkeys = chunk
+            .iter()
+            .map(|address| address.parse())
+            .collect::<Result<Vec<Pubkey>, _>>()?;
+
+        let accounts = retry(max_retries, base_delay_ms, || {
+            rpc.get_multiple_accounts_with_config(&pubkeys, config.clone())
+        })?
+        .value;
+
+        for (metadata_address, account) in chunk.iter().zip(accounts) {
+            if let Some(account) = account {
+                let (_, uri) = parse_mint_and_uri(&account.data)?;
+                println!("{},{}", metadata_address, uri);
+            }
         }
     }
 
@@ -130,14 +427,19 @@ async fn list_metadata_uris(args: Args, _opts: ListMetadataUris) -> Result<(), B
 }
 
 async fn mine_holders(args: Args, _opts: MineHolders) -> Result<(), Box<dyn Error>> {
+    let max_retries = args.max_retries;
+    let base_delay_ms = args.base_delay_ms;
+
     let timeout = Duration::from_secs(500); // TODO read from Args?
     let rpc = RpcClient::new_with_timeout(args.rpc, timeout);
 
     let db = Connection::open(args.db)?;
     db.execute(
         "CREATE TABLE IF NOT EXISTS holders (
-            mint_address   text primary key,
-            holder_address text
+            mint_address   text,
+            holder_address text,
+            amount         integer,
+            PRIMARY KEY (mint_address, holder_address)
         )",
         params![],
     )?;
@@ -148,19 +450,59 @@ async fn mine_holders(args: Args, _opts: MineHolders) -> Result<(), Box<dyn Erro
         let mint_address: String = row.get(0)?;
         let mint_address = mint_address.parse()?;
 
-        let token_accounts = get_token_largest_accounts(&rpc, mint_address)?;
-        let token_accounts = token_accounts.value;
-        for token_account in token_accounts {
-            if token_account.amount == "1" {
-                let account = rpc.get_account(&token_account.address.parse()?)?;
-                let account = Account::unpack(&mut account.data())?;
-                db.execute(
-                    "DELETE FROM holders WHERE mint_address = ?1",
-                    params![mint_address.to_string()],
-                )?;
+        db.execute(
+            "DELETE FROM holders WHERE mint_address = ?1",
+            params![mint_address.to_string()],
+        )?;
+
+        // Fast path: a supply-1 NFT has exactly one holder, so the cheap
+        // largest-accounts call is enough and avoids a full program scan.
+        let supply = retry(max_retries, base_delay_ms, || {
+            rpc.get_token_supply(&mint_address)
+        })?;
+        if supply.amount == "1" {
+            let token_accounts =
+                get_token_largest_accounts(&rpc, mint_address, max_retries, base_delay_ms)?.value;
+            for token_account in token_accounts {
+                if token_account.amount == "1" {
+                    let holder_address: Pubkey = token_account.address.parse()?;
+                    let account = retry(max_retries, base_delay_ms, || {
+                        rpc.get_account(&holder_address)
+                    })?;
+                    let token_program =
+                        TokenProgram::from_program_id(account.owner()).ok_or_else(|| {
+                            format!("{} owned by unknown token program", token_account.address)
+                        })?;
+                    let account = unpack_token_account(token_program, account.data())?;
+                    db.execute(
+                        "INSERT OR REPLACE INTO holders (mint_address, holder_address, amount) VALUES (?1, ?2, ?3)",
+                        params![mint_address.to_string(), account.owner.to_string(), account.amount as i64],
+                    )?;
+                }
+            }
+            continue;
+        }
+
+        // General path: the largest-accounts RPC caps out at 20 accounts, so
+        // fractionalized or widely-distributed supply needs a full memcmp
+        // scan across every token account for the mint, under both token
+        // programs.
+        for token_program in TokenProgram::ALL {
+            let token_accounts = get_token_accounts_for_mint(
+                &rpc,
+                token_program,
+                mint_address,
+                max_retries,
+                base_delay_ms,
+            )?;
+            for (_, account) in token_accounts {
+                let account = unpack_token_account(token_program, &account.data)?;
+                if account.amount == 0 {
+                    continue;
+                }
                 db.execute(
-                    "INSERT INTO holders (mint_address, holder_address) VALUES (?1, ?2)",
-                    params![mint_address.to_string(), account.owner.to_string()],
+                    "INSERT OR REPLACE INTO holders (mint_address, holder_address, amount) VALUES (?1, ?2, ?3)",
+                    params![mint_address.to_string(), account.owner.to_string(), account.amount as i64],
                 )?;
             }
         }
@@ -169,7 +511,50 @@ async fn mine_holders(args: Args, _opts: MineHolders) -> Result<(), Box<dyn Erro
     Ok(())
 }
 
+/// Scans every token account for `mint_address` owned by `token_program` via
+/// a `getProgramAccounts` call filtered on the mint encoded at its offset
+/// (and, for the legacy program only, on token-account-sized data),
+/// returning every holder rather than just the RPC's 20 largest.
+fn get_token_accounts_for_mint(
+    rpc: &RpcClient,
+    token_program: TokenProgram,
+    mint_address: Pubkey,
+    max_retries: usize,
+    base_delay_ms: u64,
+) -> Result<Vec<(Pubkey, solana_sdk::account::Account)>, Box<dyn Error>> {
+    // Token-2022 accounts carrying an extension (non-transferable,
+    // immutable-owner, transfer hooks, ...) are longer than the base 165
+    // bytes, so pinning DataSize to Account::LEN would silently drop them.
+    // The legacy program never has extension data, so keep the size filter
+    // there to cut down the scan.
+    let mut filters = vec![RpcFilterType::Memcmp(Memcmp {
+        offset: 0, // mint
+        bytes: MemcmpEncodedBytes::Base58(mint_address.to_string()),
+        encoding: None,
+    })];
+    if token_program == TokenProgram::Legacy {
+        filters.push(RpcFilterType::DataSize(Account::LEN as u64));
+    }
+
+    retry(max_retries, base_delay_ms, || {
+        rpc.get_program_accounts_with_config(
+            &token_program.id(),
+            RpcProgramAccountsConfig {
+                account_config: RpcAccountInfoConfig {
+                    encoding: Some(UiAccountEncoding::Base64),
+                    ..RpcAccountInfoConfig::default()
+                },
+                filters: Some(filters.clone()),
+                ..RpcProgramAccountsConfig::default()
+            },
+        )
+    })
+}
+
 async fn mine_metadata(args: Args, opts: MineMetadata) -> Result<(), Box<dyn Error>> {
+    let max_retries = args.max_retries;
+    let base_delay_ms = args.base_delay_ms;
+
     let db = Connection::open(args.db)?;
     db.execute(
         "CREATE TABLE IF NOT EXISTS creators (
@@ -182,7 +567,9 @@ async fn mine_metadata(args: Args, opts: MineMetadata) -> Result<(), Box<dyn Err
     db.execute(
         "CREATE TABLE IF NOT EXISTS metadata (
             metadata_address text primary key,
-            mint_address     text unique
+            mint_address     text unique,
+            token_program    text,
+            uri              text
         )",
         params![],
     )?;
@@ -190,54 +577,333 @@ async fn mine_metadata(args: Args, opts: MineMetadata) -> Result<(), Box<dyn Err
     let timeout = Duration::from_secs(500); // TODO read from Args?
     let rpc = RpcClient::new_with_timeout(args.rpc, timeout);
 
-    let metadata_accounts = rpc.get_program_accounts_with_config(
-        &metaplex_token_metadata::id(),
-        RpcProgramAccountsConfig {
-            account_config: RpcAccountInfoConfig {
-                encoding: Some(UiAccountEncoding::Base64Zstd),
-                ..RpcAccountInfoConfig::default()
+    let metadata_accounts = retry(max_retries, base_delay_ms, || {
+        rpc.get_program_accounts_with_config(
+            &metaplex_token_metadata::id(),
+            RpcProgramAccountsConfig {
+                account_config: RpcAccountInfoConfig {
+                    encoding: Some(UiAccountEncoding::Base64Zstd),
+                    data_slice: Some(UiDataSliceConfig {
+                        offset: 0,
+                        length: METADATA_HEADER_LEN as usize,
+                    }),
+                    ..RpcAccountInfoConfig::default()
+                },
+                filters: Some(vec![RpcFilterType::Memcmp(Memcmp {
+                    offset: 1 + // key,
+                           32 + // update auth
+                           32 + // mint
+                            4 + // name string length
+                           32 + // max name length
+                            4 + // uri string length
+                          200 + // max uri length
+                            4 + // symbol string length
+                           10 + // max symbol length
+                            2 + // seller fee basis points
+                            1 + // whether or not there is a creators vec
+                            4, // creators vec length
+                    // bytes: MemcmpEncodedBytes::Binary(opts.creator_address.to_string()),
+                    bytes: MemcmpEncodedBytes::Base58(opts.creator_address.to_string()),
+                    encoding: None,
+                })]),
+                ..RpcProgramAccountsConfig::default()
             },
-            filters: Some(vec![RpcFilterType::Memcmp(Memcmp {
-                offset: 1 + // key,
-                       32 + // update auth
-                       32 + // mint
-                        4 + // name string length
-                       32 + // max name length
-                        4 + // uri string length
-                      200 + // max uri length
-                        4 + // symbol string length
-                       10 + // max symbol length
-                        2 + // seller fee basis points
-                        1 + // whether or not there is a creators vec
-                        4, // creators vec length
-                // bytes: MemcmpEncodedBytes::Binary(opts.creator_address.to_string()),
-                bytes: MemcmpEncodedBytes::Base58(opts.creator_address.to_string()),
-                encoding: None,
-            })]),
-            ..RpcProgramAccountsConfig::default()
-        },
+        )
+    })?;
+
+    let rows = metadata_accounts
+        .into_iter()
+        .map(|(metadata_address, metadata)| {
+            let (mint, uri) = parse_mint_and_uri(metadata.data())?;
+            Ok((metadata_address, mint, uri))
+        })
+        .collect::<Result<Vec<(Pubkey, Pubkey, String)>, Box<dyn Error>>>()?;
+
+    // Resolving each mint's owning token program one account at a time would
+    // mean thousands of sequential RPC calls on large collections, the exact
+    // round-trip storm mine_metadata's scan itself is meant to avoid. Batch
+    // the lookups like list_metadata_uris does, and skip mints that come back
+    // empty (closed/burned) instead of failing the whole run.
+    let config = RpcAccountInfoConfig {
+        encoding: Some(UiAccountEncoding::Base64),
+        ..RpcAccountInfoConfig::default()
+    };
+
+    for chunk in rows.chunks(MAX_MULTIPLE_ACCOUNTS) {
+        let pubkeys = chunk.iter().map(|(_, mint, _)| *mint).collect::<Vec<_>>();
+
+        let mint_accounts = retry(max_retries, base_delay_ms, || {
+            rpc.get_multiple_accounts_with_config(&pubkeys, config.clone())
+        })?
+        .value;
+
+        for ((metadata_address, mint, uri), mint_account) in chunk.iter().zip(mint_accounts) {
+            let mint_account = match mint_account {
+                Some(mint_account) => mint_account,
+                None => {
+                    eprintln!("mint {} not found, skipping", mint);
+                    continue;
+                }
+            };
+            let token_program = match TokenProgram::from_program_id(mint_account.owner()) {
+                Some(token_program) => token_program,
+                None => {
+                    eprintln!("mint {} owned by unknown token program, skipping", mint);
+                    continue;
+                }
+            };
+
+            db.execute(
+                "INSERT OR REPLACE INTO metadata (metadata_address, mint_address, token_program, uri) VALUES (?1, ?2, ?3, ?4)",
+                params![
+                    metadata_address.to_string(),
+                    mint.to_string(),
+                    token_program.as_str(),
+                    uri,
+                ],
+            )?;
+            db.execute(
+                "INSERT OR REPLACE INTO creators (creator_address, metadata_address) VALUES (?1, ?2)",
+                params![opts.creator_address, metadata_address.to_string()],
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Max number of assets a DAS `getAssetsByCreator` page returns.
+const DAS_PAGE_LIMIT: u32 = 1000;
+
+async fn mine_compressed(args: Args, opts: MineCompressed) -> Result<(), Box<dyn Error>> {
+    let max_retries = args.max_retries;
+    let base_delay_ms = args.base_delay_ms;
+
+    let db = Connection::open(args.db)?;
+    db.execute(
+        "CREATE TABLE IF NOT EXISTS compressed_assets (
+            asset_id      text primary key,
+            owner_address text,
+            content_uri   text,
+            tree_address  text,
+            leaf_id       integer
+        )",
+        params![],
     )?;
 
-    for (metadata_address, metadata) in metadata_accounts {
-        let metadata = Metadata::deserialize(&mut metadata.data())?;
-        db.execute(
-            "INSERT OR REPLACE INTO metadata (metadata_address, mint_address) VALUES (?1, ?2)",
-            params![metadata_address.to_string(), metadata.mint.to_string()],
-        )?;
-        db.execute(
-            "INSERT OR REPLACE INTO creators (creator_address, metadata_address) VALUES (?1, ?2)",
-            params![opts.creator_address, metadata_address.to_string()],
+    let timeout = Duration::from_secs(500); // TODO read from Args?
+    let rpc = RpcClient::new_with_timeout(args.rpc, timeout);
+
+    // The DAS read API pages 1-indexed and stops once a page comes back
+    // short of the requested limit, so there's no separate "has more" flag
+    // to track.
+    let mut page = 1;
+    loop {
+        let assets = get_assets_by_creator(
+            &rpc,
+            &opts.creator_address,
+            page,
+            DAS_PAGE_LIMIT,
+            max_retries,
+            base_delay_ms,
         )?;
+        if assets.items.is_empty() {
+            break;
+        }
+
+        for asset in &assets.items {
+            db.execute(
+                "INSERT OR REPLACE INTO compressed_assets
+                    (asset_id, owner_address, content_uri, tree_address, leaf_id)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    asset.id,
+                    asset.ownership.owner,
+                    asset.content.json_uri,
+                    asset.compression.tree,
+                    asset.compression.leaf_id as i64,
+                ],
+            )?;
+        }
+
+        if assets.items.len() < DAS_PAGE_LIMIT as usize {
+            break;
+        }
+        page += 1;
     }
 
     Ok(())
 }
 
+/// Max signatures a single `getSignaturesForAddress` page returns.
+const SIGNATURES_PAGE_LIMIT: usize = 1000;
+
+async fn mine_transactions(args: Args, _opts: MineTransactions) -> Result<(), Box<dyn Error>> {
+    let max_retries = args.max_retries;
+    let base_delay_ms = args.base_delay_ms;
+
+    let timeout = Duration::from_secs(500); // TODO read from Args?
+    let rpc = RpcClient::new_with_timeout(args.rpc, timeout);
+
+    let db = Connection::open(args.db)?;
+    db.execute(
+        "CREATE TABLE IF NOT EXISTS transactions (
+            signature        text primary key,
+            mint_address     text references metadata(mint_address),
+            slot             integer,
+            block_time       integer,
+            instruction_type text
+        )",
+        params![],
+    )?;
+
+    let mut stmt = db.prepare("SELECT mint_address FROM metadata")?;
+    let mint_addresses = stmt
+        .query_map([], |row| row.get::<_, String>(0))?
+        .collect::<Result<Vec<String>, _>>()?;
+
+    for mint_address in mint_addresses {
+        let mint_pubkey: Pubkey = mint_address.parse()?;
+
+        // Resume incrementally: stop paging once we reach the newest
+        // signature already stored for this mint from a prior run.
+        let until: Option<Signature> = db
+            .query_row(
+                "SELECT signature FROM transactions WHERE mint_address = ?1 ORDER BY slot DESC LIMIT 1",
+                params![mint_address],
+                |row| row.get::<_, String>(0),
+            )
+            .optional()?
+            .map(|signature| signature.parse())
+            .transpose()?;
+
+        let mut before: Option<Signature> = None;
+        loop {
+            let config = GetConfirmedSignaturesForAddress2Config {
+                before,
+                until,
+                limit: Some(SIGNATURES_PAGE_LIMIT),
+                commitment: None,
+            };
+            let signatures = retry(max_retries, base_delay_ms, || {
+                rpc.get_signatures_for_address_with_config(&mint_pubkey, config.clone())
+            })?;
+            if signatures.is_empty() {
+                break;
+            }
+
+            for status in &signatures {
+                let signature: Signature = status.signature.parse()?;
+
+                let already_stored: Option<String> = db
+                    .query_row(
+                        "SELECT signature FROM transactions WHERE signature = ?1",
+                        params![signature.to_string()],
+                        |row| row.get(0),
+                    )
+                    .optional()?;
+                if already_stored.is_some() {
+                    continue;
+                }
+
+                // A pruned or otherwise unavailable transaction isn't a
+                // rate-limit/transport error, so `retry` won't retry it —
+                // skip it and keep mining the rest of the mint's history
+                // instead of aborting the whole run.
+                let transaction = match retry(max_retries, base_delay_ms, || {
+                    rpc.get_transaction(&signature, UiTransactionEncoding::JsonParsed)
+                }) {
+                    Ok(transaction) => transaction,
+                    Err(err) => {
+                        eprintln!("{} {}", signature, err);
+                        continue;
+                    }
+                };
+                let instruction_type = classify_instruction(&transaction);
+
+                db.execute(
+                    "INSERT OR REPLACE INTO transactions
+                        (signature, mint_address, slot, block_time, instruction_type)
+                     VALUES (?1, ?2, ?3, ?4, ?5)",
+                    params![
+                        signature.to_string(),
+                        mint_address,
+                        transaction.slot as i64,
+                        transaction.block_time,
+                        instruction_type,
+                    ],
+                )?;
+            }
+
+            before = Some(signatures.last().unwrap().signature.parse()?);
+            if signatures.len() < SIGNATURES_PAGE_LIMIT {
+                break;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Classifies a transaction's dominant instruction type by scanning its
+/// program logs for the well-known Metaplex/SPL instruction names, so the
+/// `transactions` table can distinguish mints from transfers, sales and
+/// metadata updates without fully decoding each instruction.
+fn classify_instruction(transaction: &EncodedConfirmedTransactionWithStatusMeta) -> String {
+    let logs: Vec<String> = transaction
+        .transaction
+        .meta
+        .as_ref()
+        .and_then(|meta| Option::<Vec<String>>::from(meta.log_messages.clone()))
+        .unwrap_or_default();
+
+    // A marketplace sale's inner SPL `Transfer` CPI log can be emitted
+    // before or after its `Buy`/`ExecuteSale` log depending on the program,
+    // so classifying on whichever marker appears first in `logs` is
+    // order-dependent and non-deterministic. Check the whole log set for
+    // each category instead, in priority order (a sale's transfer is still
+    // a sale, not a plain transfer).
+    let contains = |needle: &str| logs.iter().any(|log| log.contains(needle));
+
+    if contains("Instruction: MintTo") || contains("Instruction: CreateMetadataAccount") {
+        return "mint".to_owned();
+    }
+    if contains("Instruction: Buy") || contains("Instruction: ExecuteSale") {
+        return "sale".to_owned();
+    }
+    if contains("Instruction: Transfer") {
+        return "transfer".to_owned();
+    }
+    if contains("Instruction: UpdateMetadataAccount") {
+        return "update".to_owned();
+    }
+
+    "unknown".to_owned()
+}
+
 async fn rescue_slatts(args: Args, opts: RescueSlatts) -> Result<(), Box<dyn Error>> {
+    let max_retries = args.max_retries;
+    let base_delay_ms = args.base_delay_ms;
+
     let timeout = Duration::from_secs(500); // TODO read from Args?
     let rpc = RpcClient::new_with_timeout(args.rpc, timeout);
 
     let db = Connection::open(args.db)?;
+    db.execute(
+        "CREATE TABLE IF NOT EXISTS rescue_log (
+            metadata_address text primary key,
+            signature        text,
+            status           text
+        )",
+        params![],
+    )?;
+
+    let rules = load_rewrite_rules(&opts)?;
+    let update_authority = read_keypair_file(opts.update_authority.clone())?;
+    for rule in &rules {
+        validate_rule(&rule.creators, &update_authority.pubkey())?;
+    }
+
     let mut stmt = db.prepare("SELECT metadata_address, mint_address FROM metadata")?;
     let mut rows = stmt.query([])?;
     while let Some(row) = rows.next()? {
@@ -246,42 +912,56 @@ async fn rescue_slatts(args: Args, opts: RescueSlatts) -> Result<(), Box<dyn Err
 
         // let mint_address: String = row.get(1)?;
 
-        let mut tries = 0;
-        let account = loop {
-            tries += 1;
-            match rpc.get_account(&metadata_address) {
-                Ok(account) => break Some(account),
-                Err(err) => {
-                    eprint!("!");
-                    if tries > 5 {
-                        eprintln!("{} {}", metadata_address, err);
-                        break None;
-                    }
-                }
+        let already_rescued: Option<String> = db
+            .query_row(
+                "SELECT signature FROM rescue_log WHERE metadata_address = ?1",
+                params![metadata_address.to_string()],
+                |row| row.get(0),
+            )
+            .optional()?;
+        if already_rescued.is_some() {
+            continue;
+        }
+
+        let account = match retry(max_retries, base_delay_ms, || {
+            rpc.get_account(&metadata_address)
+        }) {
+            Ok(account) => Some(account),
+            Err(err) => {
+                eprintln!("{} {}", metadata_address, err);
+                None
             }
         };
 
         if let Some(account) = account {
-            let recent_blockhash = rpc.get_latest_blockhash()?;
-
             let metadata = Metadata::deserialize(&mut account.data())?;
             let data = metadata.data;
 
-            let creators = data.clone().creators.unwrap();
-            if creators.len() != 4 {
-                continue;
-            }
+            let creators = match data.creators.clone() {
+                Some(creators) => creators,
+                None => continue,
+            };
 
-            let update_authority = read_keypair_file(opts.update_authority.clone())?;
+            let rule = match find_rewrite_rule(&rules, &creators) {
+                Some(rule) => rule,
+                None => continue,
+            };
+            validate_seller_fee_basis_points(data.seller_fee_basis_points)?;
 
-            let creators: Option<Vec<Creator>> = Some(vec![
-                creators[0].clone(),
-                Creator {
-                    address: update_authority.pubkey(),
-                    verified: true,
-                    share: 100,
-                },
-            ]);
+            let new_creators = rule
+                .creators
+                .iter()
+                .map(|c| {
+                    Ok(Creator {
+                        address: c.address.parse()?,
+                        verified: c.verified,
+                        share: c.share,
+                    })
+                })
+                .collect::<Result<Vec<Creator>, Box<dyn Error>>>()?;
+
+            let recent_blockhash =
+                retry(max_retries, base_delay_ms, || rpc.get_latest_blockhash())?;
 
             let instruction = update_metadata_accounts(
                 metaplex_token_metadata::id(),
@@ -293,7 +973,7 @@ async fn rescue_slatts(args: Args, opts: RescueSlatts) -> Result<(), Box<dyn Err
                     symbol: data.symbol,
                     uri: data.uri,
                     seller_fee_basis_points: data.seller_fee_basis_points,
-                    creators,
+                    creators: Some(new_creators),
                 }),
                 None,
             );
@@ -312,8 +992,17 @@ async fn rescue_slatts(args: Args, opts: RescueSlatts) -> Result<(), Box<dyn Err
             // eprint!("{} {} {} > ", update_authority.pubkey(), metadata_address, mint_address);
             // eprintln!("{:?}", tx);
 
-            let res = rpc.simulate_transaction(&tx)?;
-            eprintln!("{} {:?}\n", metadata_address, res);
+            if opts.commit {
+                let signature = rpc.send_and_confirm_transaction_with_spinner(&tx)?;
+                db.execute(
+                    "INSERT OR REPLACE INTO rescue_log (metadata_address, signature, status) VALUES (?1, ?2, ?3)",
+                    params![metadata_address.to_string(), signature.to_string(), "confirmed"],
+                )?;
+                eprintln!("{} {}", metadata_address, signature);
+            } else {
+                let res = retry(max_retries, base_delay_ms, || rpc.simulate_transaction(&tx))?;
+                eprintln!("{} {:?}\n", metadata_address, res);
+            }
         }
     }
 
@@ -330,9 +1019,61 @@ pub struct RpcTokenAccounts {
 fn get_token_largest_accounts(
     rpc: &RpcClient,
     mint_address: Pubkey,
+    max_retries: usize,
+    base_delay_ms: u64,
 ) -> Result<Response<Vec<RpcTokenAccounts>>, Box<dyn Error>> {
     let method = "getTokenLargestAccounts";
-    let request = RpcRequest::Custom { method };
     let params = json!([mint_address.to_string()]);
-    Ok(rpc.send(request, params)?)
+    retry(max_retries, base_delay_ms, || {
+        rpc.send(RpcRequest::Custom { method }, params.clone())
+    })
+}
+
+#[derive(Debug, Deserialize)]
+struct DasAssetList {
+    items: Vec<DasAsset>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DasAsset {
+    id: String,
+    content: DasContent,
+    ownership: DasOwnership,
+    compression: DasCompression,
+}
+
+#[derive(Debug, Deserialize)]
+struct DasContent {
+    json_uri: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct DasOwnership {
+    owner: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct DasCompression {
+    tree: String,
+    leaf_id: u64,
+}
+
+fn get_assets_by_creator(
+    rpc: &RpcClient,
+    creator_address: &str,
+    page: u32,
+    limit: u32,
+    max_retries: usize,
+    base_delay_ms: u64,
+) -> Result<DasAssetList, Box<dyn Error>> {
+    let method = "getAssetsByCreator";
+    let params = json!({
+        "creatorAddress": creator_address,
+        "onlyVerified": true,
+        "page": page,
+        "limit": limit,
+    });
+    retry(max_retries, base_delay_ms, || {
+        rpc.send(RpcRequest::Custom { method }, params.clone())
+    })
 }